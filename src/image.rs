@@ -1,8 +1,10 @@
 use std::io::Cursor;
 
 use image::io::Reader;
+use image::{ImageOutputFormat, RgbaImage};
 
 use crate::kernel::{Kernel, KernelVal};
+use crate::pipeline::{Operation, Recipe};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Pixel(pub u8, pub u8, pub u8, pub u8);
@@ -28,6 +30,23 @@ impl Pixel {
             self.3.max(other.3),
         )
     }
+
+    // Alpha is left untouched and taken from `self`: subtracting it like a regular channel
+    // would turn the difference of two opaque pixels transparent instead of dark.
+    pub fn saturating_sub(&self, other: Pixel) -> Pixel {
+        Pixel(
+            self.0.saturating_sub(other.0),
+            self.1.saturating_sub(other.1),
+            self.2.saturating_sub(other.2),
+            self.3,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Binary,
+    Grayscale,
 }
 
 #[derive(Clone)]
@@ -38,19 +57,23 @@ pub struct Image {
 }
 
 impl Image {
-    pub fn new_with_data(data: Vec<u8>) -> Self {
+    pub fn new_with_data(data: Vec<u8>, mode: Mode) -> Self {
         let reader = Reader::new(Cursor::new(&data[..]))
             .with_guessed_format()
             .expect("Couldn't guess file format.");
 
         let image = reader.decode().expect("Unable to decode image.");
 
-        Self {
+        let image = Self {
             data: image.to_rgba8().to_vec(),
             width: image.width(),
             height: image.height(),
+        };
+
+        match mode {
+            Mode::Binary => image.binarize(128),
+            Mode::Grayscale => image,
         }
-        .binarize(128)
     }
 
     pub fn get_bitmap_data(&self) -> &Vec<u8> {
@@ -65,6 +88,18 @@ impl Image {
         self.height
     }
 
+    pub fn to_png_bytes(&self) -> Vec<u8> {
+        let buffer = RgbaImage::from_raw(self.width, self.height, self.data.clone())
+            .expect("Image data doesn't match its declared dimensions.");
+
+        let mut bytes = Cursor::new(Vec::new());
+        buffer
+            .write_to(&mut bytes, ImageOutputFormat::Png)
+            .expect("Unable to encode image as PNG.");
+
+        bytes.into_inner()
+    }
+
     pub fn get_pixel(&self, x: u32, y: u32) -> Pixel {
         let index = (y as usize * self.width as usize + x as usize) * 4;
 
@@ -114,15 +149,55 @@ impl Image {
     }
 
     pub fn open(&self, kernel: Kernel) -> Image {
-        let mut okernel = Kernel::new();
-        okernel.change_dimension(kernel.get_dimension()).unwrap();
-        self.erode(okernel.clone()).dilate(okernel)
+        self.erode(kernel.clone()).dilate(kernel)
     }
 
     pub fn close(&self, kernel: Kernel) -> Image {
-        let mut okernel = Kernel::new();
-        okernel.change_dimension(kernel.get_dimension()).unwrap();
-        self.dilate(okernel.clone()).erode(okernel)
+        self.dilate(kernel.clone()).erode(kernel)
+    }
+
+    pub fn morphological_gradient(&self, kernel: Kernel) -> Image {
+        self.dilate(kernel.clone()).subtract(&self.erode(kernel))
+    }
+
+    pub fn top_hat(&self, kernel: Kernel) -> Image {
+        self.subtract(&self.open(kernel))
+    }
+
+    pub fn black_hat(&self, kernel: Kernel) -> Image {
+        self.close(kernel).subtract(self)
+    }
+
+    fn subtract(&self, other: &Image) -> Image {
+        let mut result = self.clone();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self.get_pixel(x, y).saturating_sub(other.get_pixel(x, y));
+                result.set_pixel(x, y, pixel);
+            }
+        }
+
+        result
+    }
+
+    pub fn apply_pipeline(&self, recipe: &Recipe) -> Result<Image, Box<dyn std::error::Error>> {
+        let mut image = self.clone();
+
+        for step in &recipe.steps {
+            let kernel = step.element.to_kernel()?;
+            image = match step.op {
+                Operation::Dilate => image.dilate(kernel),
+                Operation::Erode => image.erode(kernel),
+                Operation::Open => image.open(kernel),
+                Operation::Close => image.close(kernel),
+                Operation::HitOrMiss => image.hit_or_miss(kernel),
+                Operation::Thinning => image.thinning(kernel),
+                Operation::Thickening => image.thickening(kernel),
+            };
+        }
+
+        Ok(image)
     }
 
     pub fn hit_or_miss(&self, kernel: Kernel) -> Image {
@@ -167,7 +242,86 @@ impl Image {
         result
     }
 
+    pub fn skeletonize(&self) -> Image {
+        let mut result = self.clone();
+
+        loop {
+            let changed_first = result.zhang_suen_pass(1);
+            let changed_second = result.zhang_suen_pass(2);
+
+            if !changed_first && !changed_second {
+                break;
+            }
+        }
+
+        result
+    }
+
+    fn is_foreground(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return false;
+        }
+
+        self.get_pixel(x as u32, y as u32) == BLACK
+    }
+
+    fn zhang_suen_pass(&mut self, sub_iteration: u8) -> bool {
+        let mut to_delete = Vec::new();
+
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if !self.is_foreground(x, y) {
+                    continue;
+                }
+
+                let p2 = self.is_foreground(x, y - 1);
+                let p3 = self.is_foreground(x + 1, y - 1);
+                let p4 = self.is_foreground(x + 1, y);
+                let p5 = self.is_foreground(x + 1, y + 1);
+                let p6 = self.is_foreground(x, y + 1);
+                let p7 = self.is_foreground(x - 1, y + 1);
+                let p8 = self.is_foreground(x - 1, y);
+                let p9 = self.is_foreground(x - 1, y - 1);
+
+                let b = [p2, p3, p4, p5, p6, p7, p8, p9]
+                    .iter()
+                    .filter(|neighbor| **neighbor)
+                    .count();
+                if !(2..=6).contains(&b) {
+                    continue;
+                }
+
+                let cyclic = [p2, p3, p4, p5, p6, p7, p8, p9, p2];
+                let a = cyclic.windows(2).filter(|pair| !pair[0] && pair[1]).count();
+                if a != 1 {
+                    continue;
+                }
+
+                let deletable = if sub_iteration == 1 {
+                    !(p2 && p4 && p6) && !(p4 && p6 && p8)
+                } else {
+                    !(p2 && p4 && p8) && !(p2 && p6 && p8)
+                };
+
+                if deletable {
+                    to_delete.push((x as u32, y as u32));
+                }
+            }
+        }
+
+        let changed = !to_delete.is_empty();
+        for (x, y) in to_delete {
+            self.set_pixel(x, y, WHITE);
+        }
+
+        changed
+    }
+
     fn dilate_or_erode(&self, kernel: Kernel, erode: bool) -> Self {
+        if kernel.is_flat() {
+            return self.dilate_or_erode_flat(kernel.get_dimension(), erode);
+        }
+
         let mut new_image = self.clone();
 
         for y in 0..self.height {
@@ -180,6 +334,48 @@ impl Image {
         new_image
     }
 
+    // Fast path for a fully-`One` rectangular kernel: the window extremum is separable,
+    // so each row and column can be swept in O(1) per pixel with van Herk-Gil-Werman
+    // instead of re-scanning the whole k*k kernel for every output pixel.
+    fn dilate_or_erode_flat(&self, kernel_dim: u32, erode: bool) -> Self {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let mut result = self.clone();
+
+        let mut row_buf = vec![0u8; width];
+        let mut column_buf = vec![0u8; height];
+
+        for channel in 0..4 {
+            let mut plane: Vec<u8> = (0..width * height)
+                .map(|i| self.data[i * 4 + channel])
+                .collect();
+
+            for y in 0..height {
+                let row = &mut plane[y * width..(y + 1) * width];
+                row_buf.copy_from_slice(row);
+                let filtered = van_herk_gil_werman_1d(&row_buf, kernel_dim as usize, erode);
+                row.copy_from_slice(&filtered);
+            }
+
+            for x in 0..width {
+                for y in 0..height {
+                    column_buf[y] = plane[y * width + x];
+                }
+
+                let filtered = van_herk_gil_werman_1d(&column_buf, kernel_dim as usize, erode);
+                for y in 0..height {
+                    plane[y * width + x] = filtered[y];
+                }
+            }
+
+            for (i, value) in plane.into_iter().enumerate() {
+                result.data[i * 4 + channel] = value;
+            }
+        }
+
+        result
+    }
+
     fn get_min_or_max(&self, row: u32, col: u32, kernel: &Kernel, erode: bool) -> Pixel {
         let kernel_dim = kernel.get_dimension();
         let kernel_center = (kernel_dim as i32 - 1) / 2;
@@ -276,3 +472,51 @@ impl Image {
         WHITE
     }
 }
+
+// Van Herk-Gil-Werman running min/max over a 1D line with a flat window of length `k`,
+// centered on each sample. The line is padded with the identity element for the chosen
+// extremum (0 for max, 255 for min) so a window hanging off either edge behaves as if
+// those out-of-range samples were simply absent from the kernel.
+fn van_herk_gil_werman_1d(line: &[u8], k: usize, erode: bool) -> Vec<u8> {
+    let n = line.len();
+    if n == 0 || k <= 1 {
+        return line.to_vec();
+    }
+
+    let extremum = |a: u8, b: u8| if erode { a.min(b) } else { a.max(b) };
+    let identity = if erode { u8::MAX } else { 0 };
+
+    let radius = (k - 1) / 2;
+    let padded_len = n + 2 * radius;
+    let padded: Vec<u8> = (0..padded_len)
+        .map(|i| {
+            if i < radius || i >= radius + n {
+                identity
+            } else {
+                line[i - radius]
+            }
+        })
+        .collect();
+
+    let mut g = vec![0u8; padded_len];
+    let mut h = vec![0u8; padded_len];
+
+    let mut block_start = 0;
+    while block_start < padded_len {
+        let block_end = (block_start + k).min(padded_len);
+
+        g[block_start] = padded[block_start];
+        for i in (block_start + 1)..block_end {
+            g[i] = extremum(g[i - 1], padded[i]);
+        }
+
+        h[block_end - 1] = padded[block_end - 1];
+        for i in (block_start..block_end - 1).rev() {
+            h[i] = extremum(h[i + 1], padded[i]);
+        }
+
+        block_start = block_end;
+    }
+
+    (0..n).map(|i| extremum(g[i + k - 1], h[i])).collect()
+}
@@ -45,6 +45,98 @@ impl Kernel {
         let index = (y as usize * self.dimension as usize + x as usize) as usize;
         self.data[index]
     }
+
+    pub fn disk(radius: u32) -> Self {
+        let dimension = 2 * radius + 1;
+        let mut kernel = Self::new();
+        kernel.change_dimension(dimension).unwrap();
+
+        let center = radius as f32;
+        for y in 0..dimension {
+            for x in 0..dimension {
+                let dx = x as f32 - center;
+                let dy = y as f32 - center;
+                let val = if (dx * dx + dy * dy).sqrt() <= radius as f32 {
+                    KernelVal::One
+                } else {
+                    KernelVal::Zero
+                };
+                kernel.set(x, y, val);
+            }
+        }
+
+        kernel
+    }
+
+    pub fn cross(size: u32) -> Self {
+        let dimension = 2 * size + 1;
+        let mut kernel = Self::new();
+        kernel.change_dimension(dimension).unwrap();
+
+        for y in 0..dimension {
+            for x in 0..dimension {
+                let val = if x == size || y == size {
+                    KernelVal::One
+                } else {
+                    KernelVal::Zero
+                };
+                kernel.set(x, y, val);
+            }
+        }
+
+        kernel
+    }
+
+    pub fn diamond(size: u32) -> Self {
+        let dimension = 2 * size + 1;
+        let mut kernel = Self::new();
+        kernel.change_dimension(dimension).unwrap();
+
+        let center = size as i32;
+        for y in 0..dimension {
+            for x in 0..dimension {
+                let dist = (x as i32 - center).abs() + (y as i32 - center).abs();
+                let val = if dist <= size as i32 {
+                    KernelVal::One
+                } else {
+                    KernelVal::Zero
+                };
+                kernel.set(x, y, val);
+            }
+        }
+
+        kernel
+    }
+
+    pub fn line(length: u32, angle_degrees: f32) -> Self {
+        let dimension = if length % 2 == 0 { length + 1 } else { length };
+        let mut kernel = Self::new();
+        kernel.change_dimension(dimension).unwrap();
+
+        for y in 0..dimension {
+            for x in 0..dimension {
+                kernel.set(x, y, KernelVal::Zero);
+            }
+        }
+
+        let center = (dimension as i32 - 1) / 2;
+        let radians = angle_degrees.to_radians();
+        let (dx, dy) = (radians.cos(), radians.sin());
+
+        for step in -center..=center {
+            let x = (center as f32 + step as f32 * dx).round() as i32;
+            let y = (center as f32 + step as f32 * dy).round() as i32;
+            if x >= 0 && x < dimension as i32 && y >= 0 && y < dimension as i32 {
+                kernel.set(x as u32, y as u32, KernelVal::One);
+            }
+        }
+
+        kernel
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.data.iter().all(|val| *val == KernelVal::One)
+    }
 }
 
 
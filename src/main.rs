@@ -1,11 +1,16 @@
 mod image;
 mod kernel;
+mod pipeline;
 
 use gloo_events::EventListener;
-use js_sys::Uint8Array;
+use image::Mode;
+use js_sys::{Array, Uint8Array};
 use kernel::{Kernel, KernelVal};
+use pipeline::Recipe;
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlInputElement, ImageData};
+use web_sys::{
+    Blob, BlobPropertyBag, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement, ImageData, Url,
+};
 use yew::prelude::*;
 
 enum Msg {
@@ -19,8 +24,46 @@ enum Msg {
     HitAndMiss,
     Thinning,
     Thickening,
+    Skeletonize,
+    Gradient,
+    TopHat,
+    BlackHat,
+    Download,
+    ToggleGrayscale,
     ToggleKernel(u32, u32, bool),
     ToggleKernelDontCare(u32, u32),
+    PipelineTextChanged(InputEvent),
+    RunPipeline,
+    ShapeChanged(Event),
+    AngleChanged(InputEvent),
+    GenerateKernel,
+}
+
+fn trigger_png_download(bytes: &[u8], filename: &str) {
+    let array = Uint8Array::from(bytes);
+    let parts = Array::new();
+    parts.push(&array);
+
+    let mut properties = BlobPropertyBag::new();
+    properties.type_("image/png");
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &properties)
+        .expect("Unable to create blob from PNG bytes.");
+
+    let url = Url::create_object_url_with_blob(&blob).expect("Unable to create object URL.");
+
+    let document = web_sys::window().unwrap().document().unwrap();
+    let anchor = document
+        .create_element("a")
+        .unwrap()
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .unwrap();
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    Url::revoke_object_url(&url).unwrap_or_else(|err| {
+        log::error!("Error: {:?}", err);
+    });
 }
 
 struct App {
@@ -33,6 +76,10 @@ struct App {
     radius: u32,
     kernel: kernel::Kernel,
     background_kernel: kernel::Kernel,
+    pipeline_text: String,
+    shape: String,
+    angle: f32,
+    grayscale_mode: bool,
 }
 
 impl Component for App {
@@ -66,6 +113,10 @@ impl Component for App {
             radius,
             kernel,
             background_kernel,
+            pipeline_text: String::new(),
+            shape: "square".to_string(),
+            angle: 0.0,
+            grayscale_mode: false,
         }
     }
 
@@ -127,6 +178,9 @@ impl Component for App {
         html! {
             <div>
                 <div>
+                    <label>{"Grayscale mode"}</label>
+                    <input type="checkbox" checked={self.grayscale_mode}
+                        onclick={ctx.link().callback(|_| Msg::ToggleGrayscale)} />
                     <input type="file" onchange={ctx.link().callback(|event: Event| Msg::FileUpload(event))} />
                     if self.is_loading {
                         <span>{"Loading image..."}</span>
@@ -136,9 +190,18 @@ impl Component for App {
                         <button onclick={ctx.link().callback(|_| Msg::Erode)}>{"Erode"}</button>
                         <button onclick={ctx.link().callback(|_| Msg::Open)}>{"Open"}</button>
                         <button onclick={ctx.link().callback(|_| Msg::Close)}>{"Close"}</button>
-                        <button onclick={ctx.link().callback(|_| Msg::HitAndMiss)}>{"Hit or Miss"}</button>
-                        <button onclick={ctx.link().callback(|_| Msg::Thinning)}>{"Thinning"}</button>
-                        <button onclick={ctx.link().callback(|_| Msg::Thickening)}>{"Thickening"}</button>
+                        if !self.grayscale_mode {
+                            <button onclick={ctx.link().callback(|_| Msg::HitAndMiss)}>{"Hit or Miss"}</button>
+                            <button onclick={ctx.link().callback(|_| Msg::Thinning)}>{"Thinning"}</button>
+                            <button onclick={ctx.link().callback(|_| Msg::Thickening)}>{"Thickening"}</button>
+                            <button onclick={ctx.link().callback(|_| Msg::Skeletonize)}>{"Skeletonize"}</button>
+                        } else {
+                            <span>{"Hit or Miss / Thinning / Thickening / Skeletonize require binary mode"}</span>
+                        }
+                        <button onclick={ctx.link().callback(|_| Msg::Gradient)}>{"Gradient"}</button>
+                        <button onclick={ctx.link().callback(|_| Msg::TopHat)}>{"Top Hat"}</button>
+                        <button onclick={ctx.link().callback(|_| Msg::BlackHat)}>{"Black Hat"}</button>
+                        <button onclick={ctx.link().callback(|_| Msg::Download)}>{"Save PNG"}</button>
                     }
                 </div>
                 <canvas
@@ -151,10 +214,37 @@ impl Component for App {
                         value={self.kernel.get_dimension().to_string()}
                         oninput={ctx.link().callback(|event: InputEvent| Msg::RadiusChanged(event))} />
                 </div>
+                <div>
+                    <label>{"Structuring element shape"}</label>
+                    <select onchange={ctx.link().callback(|event: Event| Msg::ShapeChanged(event))}>
+                        <option value="square" selected={self.shape == "square"}>{"Square (manual)"}</option>
+                        <option value="disk" selected={self.shape == "disk"}>{"Disk"}</option>
+                        <option value="cross" selected={self.shape == "cross"}>{"Cross"}</option>
+                        <option value="diamond" selected={self.shape == "diamond"}>{"Diamond"}</option>
+                        <option value="line" selected={self.shape == "line"}>{"Line"}</option>
+                    </select>
+                    if self.shape == "line" {
+                        <label>{"Angle (degrees)"}</label>
+                        <input type="number" value={self.angle.to_string()}
+                            oninput={ctx.link().callback(|event: InputEvent| Msg::AngleChanged(event))} />
+                    }
+                    <button onclick={ctx.link().callback(|_| Msg::GenerateKernel)}>{"Generate kernel"}</button>
+                </div>
                 <div>
                     <label>{"Kernel"}</label>
                     {display_kernel(kernel, false)}
                 </div>
+                if self.original_image.is_some() {
+                    <div>
+                        <label>{"Pipeline recipe (YAML or JSON)"}</label>
+                        <textarea
+                            rows="8"
+                            cols="40"
+                            value={self.pipeline_text.clone()}
+                            oninput={ctx.link().callback(|event: InputEvent| Msg::PipelineTextChanged(event))} />
+                        <button onclick={ctx.link().callback(|_| Msg::RunPipeline)}>{"Run pipeline"}</button>
+                    </div>
+                }
             </div>
         }
     }
@@ -184,8 +274,14 @@ impl Component for App {
             Msg::FileLoaded(data) => {
                 self.is_loading = false;
                 self.image_data = Some(data);
+                let mode = if self.grayscale_mode {
+                    Mode::Grayscale
+                } else {
+                    Mode::Binary
+                };
                 self.original_image = Some(image::Image::new_with_data(
                     self.image_data.clone().unwrap(),
+                    mode,
                 ));
                 self.image = Some(self.original_image.clone().unwrap());
 
@@ -259,6 +355,100 @@ impl Component for App {
 
                 true
             }
+            Msg::Skeletonize => {
+                if let Some(image) = &self.image {
+                    self.image = Some(image.skeletonize());
+                }
+
+                true
+            }
+            Msg::Gradient => {
+                if let Some(image) = &self.image {
+                    self.image = Some(image.morphological_gradient(self.kernel.clone()));
+                }
+
+                true
+            }
+            Msg::TopHat => {
+                if let Some(image) = &self.image {
+                    self.image = Some(image.top_hat(self.kernel.clone()));
+                }
+
+                true
+            }
+            Msg::BlackHat => {
+                if let Some(image) = &self.image {
+                    self.image = Some(image.black_hat(self.kernel.clone()));
+                }
+
+                true
+            }
+            Msg::ToggleGrayscale => {
+                self.grayscale_mode = !self.grayscale_mode;
+
+                if let Some(data) = self.image_data.clone() {
+                    let mode = if self.grayscale_mode {
+                        Mode::Grayscale
+                    } else {
+                        Mode::Binary
+                    };
+                    self.original_image = Some(image::Image::new_with_data(data, mode));
+                    self.image = self.original_image.clone();
+                }
+
+                true
+            }
+            Msg::Download => {
+                if let Some(image) = &self.image {
+                    trigger_png_download(&image.to_png_bytes(), "morphop.png");
+                }
+
+                false
+            }
+            Msg::PipelineTextChanged(event) => {
+                let target: HtmlTextAreaElement = event.target().unwrap().dyn_into().unwrap();
+                self.pipeline_text = target.value();
+
+                true
+            }
+            Msg::RunPipeline => {
+                if let Some(image) = &self.image {
+                    match Recipe::parse(&self.pipeline_text).and_then(|recipe| image.apply_pipeline(&recipe)) {
+                        Ok(result) => self.image = Some(result),
+                        Err(err) => log::error!("Error: {}", err),
+                    }
+                }
+
+                true
+            }
+            Msg::ShapeChanged(event) => {
+                let target: HtmlSelectElement = event.target().unwrap().dyn_into().unwrap();
+                self.shape = target.value();
+
+                true
+            }
+            Msg::AngleChanged(event) => {
+                let target: HtmlInputElement = event.target().unwrap().dyn_into().unwrap();
+                self.angle = target.value_as_number() as f32;
+
+                true
+            }
+            Msg::GenerateKernel => {
+                let size = (self.kernel.get_dimension() - 1) / 2;
+                let generated = match self.shape.as_str() {
+                    "disk" => Some(Kernel::disk(size)),
+                    "cross" => Some(Kernel::cross(size)),
+                    "diamond" => Some(Kernel::diamond(size)),
+                    "line" => Some(Kernel::line(self.kernel.get_dimension(), self.angle)),
+                    _ => None,
+                };
+
+                if let Some(generated) = generated {
+                    self.kernel = generated;
+                }
+
+                true
+            }
             Msg::ToggleKernel(x, y, _) => {
                 let current = self.kernel.get(x, y);
                 if current == KernelVal::One {
@@ -0,0 +1,93 @@
+use std::error::Error;
+
+use serde::Deserialize;
+
+use crate::kernel::{Kernel, KernelVal};
+
+#[derive(Debug, Deserialize)]
+pub struct Recipe {
+    pub steps: Vec<Step>,
+}
+
+impl Recipe {
+    pub fn parse(text: &str) -> Result<Recipe, Box<dyn Error>> {
+        if let Ok(recipe) = serde_json::from_str::<Recipe>(text) {
+            return Ok(recipe);
+        }
+
+        Ok(serde_yaml::from_str(text)?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    pub op: Operation,
+    pub element: ElementSpec,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Dilate,
+    Erode,
+    Open,
+    Close,
+    HitOrMiss,
+    Thinning,
+    Thickening,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ElementSpec {
+    Grid { grid: Vec<String> },
+    Named { shape: String, size: u32 },
+}
+
+impl ElementSpec {
+    pub fn to_kernel(&self) -> Result<Kernel, Box<dyn Error>> {
+        match self {
+            ElementSpec::Grid { grid } => grid_to_kernel(grid),
+            ElementSpec::Named { shape, size } => named_kernel(shape, *size),
+        }
+    }
+}
+
+fn grid_to_kernel(rows: &[String]) -> Result<Kernel, Box<dyn Error>> {
+    let dimension = rows.len() as u32;
+
+    if dimension % 2 == 0 || rows.iter().any(|row| row.chars().count() as u32 != dimension) {
+        return Err(format!(
+            "Recipe grid must be a square with an odd number of rows, got {} row(s).",
+            dimension
+        )
+        .into());
+    }
+
+    let mut kernel = Kernel::new();
+    kernel.change_dimension(dimension)?;
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, cell) in row.chars().enumerate() {
+            let val = match cell {
+                '1' => KernelVal::One,
+                '0' => KernelVal::Zero,
+                'x' | 'X' => KernelVal::DontCare,
+                other => return Err(format!("Unknown kernel cell '{}'.", other).into()),
+            };
+            kernel.set(x as u32, y as u32, val);
+        }
+    }
+
+    Ok(kernel)
+}
+
+fn named_kernel(shape: &str, size: u32) -> Result<Kernel, Box<dyn Error>> {
+    match shape {
+        "disk" => Ok(Kernel::disk(size)),
+        "cross" => Ok(Kernel::cross(size)),
+        "diamond" => Ok(Kernel::diamond(size)),
+        "line" => Ok(Kernel::line(2 * size + 1, 0.0)),
+        other => Err(format!("Unknown structuring element shape '{}'.", other).into()),
+    }
+}